@@ -9,7 +9,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         criterion::PlotConfiguration::default().summary_scale(criterion::AxisScale::Logarithmic),
     );
 
-    group.bench_function("nominal_merge", |b| b.iter(|| load_and_merge_documents()));
+    group.bench_function("nominal_merge", |b| b.iter(load_and_merge_documents));
 
     group.finish();
 }
@@ -0,0 +1,177 @@
+use crate::merger::{self, file_label};
+use crate::metadata::MetadataOptions;
+use crate::outline::OutlineOptions;
+use crate::page_range::PageRange;
+use lopdf::{Document, Error};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// One input to a merge: a path to load lazily, a raw byte buffer to parse with
+// `Document::load_mem`, or a `Document` the caller has already parsed - each with its own
+// page selection.
+enum MergeInput {
+    Path(PathBuf, PageRange),
+    Bytes(Vec<u8>, PageRange),
+    Document(Box<Document>, PageRange),
+}
+
+impl MergeInput {
+    fn into_document_label_and_range(self, index: usize) -> Result<(Document, String, PageRange), Error> {
+        match self {
+            MergeInput::Path(path, range) => {
+                let label = file_label(&path.to_string_lossy());
+                let document = Document::load(&path)?;
+                Ok((document, label, range))
+            }
+            MergeInput::Bytes(bytes, range) => {
+                let document = Document::load_mem(&bytes)?;
+                Ok((document, format!("input-{index}"), range))
+            }
+            MergeInput::Document(document, range) => {
+                Ok((*document, format!("input-{index}"), range))
+            }
+        }
+    }
+}
+
+/// Builds a merged PDF from any mix of file paths, in-memory byte buffers, and already-parsed
+/// `lopdf::Document`s. This is what makes the crate usable as a server-side dependency - merging
+/// uploaded byte streams, for instance - rather than only as a path-based CLI.
+pub struct MergeBuilder {
+    inputs: Vec<MergeInput>,
+    version: String,
+    compress: bool,
+    outline_options: OutlineOptions,
+    dedup_resources: bool,
+    metadata_options: MetadataOptions,
+}
+
+impl Default for MergeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MergeBuilder {
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            version: "1.5".to_string(),
+            compress: true,
+            outline_options: OutlineOptions::default(),
+            dedup_resources: false,
+            metadata_options: MetadataOptions::InheritFirst,
+        }
+    }
+
+    /// Adds a file to merge, read from disk when [`build`](Self::build) runs.
+    pub fn add_path(self, path: impl AsRef<Path>) -> Self {
+        self.add_path_with_range(path, PageRange::All)
+    }
+
+    /// Adds a file to merge, selecting and ordering only the pages `page_range` refers to.
+    pub fn add_path_with_range(mut self, path: impl AsRef<Path>, page_range: PageRange) -> Self {
+        self.inputs
+            .push(MergeInput::Path(path.as_ref().to_path_buf(), page_range));
+        self
+    }
+
+    /// Adds a file to merge, already held in memory (e.g. an uploaded byte stream).
+    pub fn add_bytes(self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.add_bytes_with_range(bytes, PageRange::All)
+    }
+
+    /// Adds a file to merge from memory, selecting and ordering only the pages `page_range`
+    /// refers to.
+    pub fn add_bytes_with_range(
+        mut self,
+        bytes: impl Into<Vec<u8>>,
+        page_range: PageRange,
+    ) -> Self {
+        self.inputs
+            .push(MergeInput::Bytes(bytes.into(), page_range));
+        self
+    }
+
+    /// Adds a file to merge that the caller has already parsed with `lopdf`.
+    pub fn add_document(self, document: Document) -> Self {
+        self.add_document_with_range(document, PageRange::All)
+    }
+
+    /// Adds an already-parsed document to merge, selecting and ordering only the pages
+    /// `page_range` refers to.
+    pub fn add_document_with_range(mut self, document: Document, page_range: PageRange) -> Self {
+        self.inputs
+            .push(MergeInput::Document(Box::new(document), page_range));
+        self
+    }
+
+    /// Sets the PDF version of the merged output (default `"1.5"`).
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Controls whether the merged output is compressed (default `true`).
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn outline_options(mut self, outline_options: OutlineOptions) -> Self {
+        self.outline_options = outline_options;
+        self
+    }
+
+    /// Deduplicates identical shared resources (fonts, ICC profiles, images) across inputs
+    /// (default `false`; costs a full object-graph rewrite).
+    pub fn dedup_resources(mut self, enabled: bool) -> Self {
+        self.dedup_resources = enabled;
+        self
+    }
+
+    /// Controls the merged document's `/Info` dictionary (default: inherit the first input's).
+    pub fn metadata_options(mut self, metadata_options: MetadataOptions) -> Self {
+        self.metadata_options = metadata_options;
+        self
+    }
+
+    /// Runs the merge and returns the resulting `Document` without writing it anywhere.
+    pub fn build(self) -> Result<Document, Error> {
+        let mut documents = Vec::with_capacity(self.inputs.len());
+        let mut labels = Vec::with_capacity(self.inputs.len());
+        let mut page_ranges = Vec::with_capacity(self.inputs.len());
+
+        for (index, input) in self.inputs.into_iter().enumerate() {
+            let (document, label, page_range) = input.into_document_label_and_range(index)?;
+            documents.push(document);
+            labels.push(label);
+            page_ranges.push(page_range);
+        }
+
+        merger::merge_loaded_documents(
+            documents,
+            labels,
+            page_ranges,
+            &self.version,
+            self.compress,
+            self.outline_options,
+            self.dedup_resources,
+            self.metadata_options,
+        )
+    }
+
+    /// Runs the merge and writes the resulting PDF into `writer`.
+    pub fn write_to<W: Write>(self, mut writer: W) -> Result<(), Error> {
+        let mut document = self.build()?;
+        document.save_to(&mut writer)?;
+        Ok(())
+    }
+
+    /// Runs the merge and returns the resulting PDF as an in-memory byte buffer.
+    pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,118 @@
+use lopdf::{Dictionary, Object, ObjectId};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+/// Deduplicates objects that are byte-for-byte identical across source documents - shared fonts,
+/// ICC profiles, and images are the common case - before they are renumbered and compressed.
+/// Rewrites every `Object::Reference` in `pages` and `objects` to point at a single canonical id
+/// per distinct content, then drops the now-redundant objects.
+pub fn dedup_resources(
+    pages: &mut BTreeMap<ObjectId, Object>,
+    objects: &mut BTreeMap<ObjectId, Object>,
+) {
+    // Keyed by hash first, but a hash match alone isn't proof of equality - two different objects
+    // that happen to collide would otherwise silently replace one with the other. Each bucket
+    // keeps every distinct object seen under that hash and only treats a later object as
+    // redundant once it's actually equal to one already in the bucket.
+    let mut canonical_by_hash: HashMap<u64, Vec<(ObjectId, &Object)>> = HashMap::new();
+    let mut replacements: HashMap<ObjectId, ObjectId> = HashMap::new();
+
+    for (&id, object) in objects.iter() {
+        let bucket = canonical_by_hash.entry(hash_object(object)).or_default();
+        match bucket.iter().find(|(_, existing)| *existing == object) {
+            Some(&(canonical_id, _)) => {
+                replacements.insert(id, canonical_id);
+            }
+            None => bucket.push((id, object)),
+        }
+    }
+
+    if replacements.is_empty() {
+        return;
+    }
+
+    objects.retain(|id, _| !replacements.contains_key(id));
+
+    for object in objects.values_mut() {
+        rewrite_references(object, &replacements);
+    }
+    for object in pages.values_mut() {
+        rewrite_references(object, &replacements);
+    }
+}
+
+fn rewrite_references(object: &mut Object, replacements: &HashMap<ObjectId, ObjectId>) {
+    match object {
+        Object::Reference(id) => {
+            if let Some(&canonical) = replacements.get(id) {
+                *id = canonical;
+            }
+        }
+        Object::Array(items) => {
+            for item in items {
+                rewrite_references(item, replacements);
+            }
+        }
+        Object::Dictionary(dict) => rewrite_dict_references(dict, replacements),
+        Object::Stream(stream) => rewrite_dict_references(&mut stream.dict, replacements),
+        _ => {}
+    }
+}
+
+fn rewrite_dict_references(dict: &mut Dictionary, replacements: &HashMap<ObjectId, ObjectId>) {
+    for (_, value) in dict.iter_mut() {
+        rewrite_references(value, replacements);
+    }
+}
+
+// Canonical content hash: a stream hashes its dictionary (minus `/Length`, which is derived from
+// the bytes rather than being content) plus its raw data; a dictionary hashes its key/value pairs
+// in sorted order so key ordering doesn't affect the result; a reference hashes the id it points
+// at. That last point means this is a single pass, not a fixpoint - two composite dictionaries
+// that reference equivalent-but-not-yet-deduplicated subgraphs won't collapse, only objects that
+// are already identical down to the ids they reference will. That's enough to catch the common
+// case of a font file or ICC profile stream embedded verbatim in every input.
+fn hash_object(object: &Object) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_into(object, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_into(object: &Object, hasher: &mut impl Hasher) {
+    match object {
+        Object::Stream(stream) => {
+            hash_dict(&stream.dict, hasher, &[b"Length"]);
+            stream.content.hash(hasher);
+        }
+        Object::Dictionary(dict) => hash_dict(dict, hasher, &[]),
+        Object::Array(items) => {
+            for item in items {
+                hash_into(item, hasher);
+            }
+        }
+        Object::Reference(id) => id.hash(hasher),
+        Object::Null => {}
+        Object::Boolean(value) => value.hash(hasher),
+        Object::Integer(value) => value.hash(hasher),
+        Object::Real(value) => value.to_bits().hash(hasher),
+        Object::Name(name) => name.hash(hasher),
+        Object::String(bytes, format) => {
+            bytes.hash(hasher);
+            matches!(format, lopdf::StringFormat::Hexadecimal).hash(hasher);
+        }
+    }
+}
+
+fn hash_dict(dict: &Dictionary, hasher: &mut impl Hasher, skip_keys: &[&[u8]]) {
+    let mut entries: Vec<_> = dict
+        .iter()
+        .filter(|(key, _)| !skip_keys.contains(&key.as_slice()))
+        .collect();
+    entries.sort_by_key(|(key, _)| key.to_vec());
+
+    for (key, value) in entries {
+        key.hash(hasher);
+        hash_into(value, hasher);
+    }
+}
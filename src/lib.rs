@@ -0,0 +1,15 @@
+pub mod builder;
+mod dedup;
+pub mod merger;
+pub mod metadata;
+pub mod outline;
+pub mod page_range;
+
+pub use builder::MergeBuilder;
+pub use merger::{
+    merge_pdfs, merge_pdfs_with_options, merge_pdfs_with_outline_options,
+    merge_pdfs_with_page_ranges,
+};
+pub use metadata::{DocumentInfo, MetadataOptions};
+pub use outline::OutlineOptions;
+pub use page_range::PageRange;
@@ -1,5 +1,4 @@
-mod merger;
-
+use pdf_merger_lig::merger;
 use std::error::Error;
 use std::fs::File;
 
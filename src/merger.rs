@@ -1,65 +1,275 @@
-use lopdf::{Document, Error, Object, ObjectId};
+use crate::dedup;
+use crate::metadata::{self, MetadataOptions};
+use crate::outline::{self, OutlineOptions, SourceOutline};
+use crate::page_range::PageRange;
+use lopdf::{Dictionary, Document, Error, Object, ObjectId};
 use rayon::prelude::*;
 use std::collections::BTreeMap;
 use std::io;
+use std::path::Path;
 
-type DocumentMappings = (BTreeMap<ObjectId, Object>, BTreeMap<ObjectId, Object>);
 type PdfStructureComponents = ((ObjectId, Object), (ObjectId, Object));
 
+const DEFAULT_VERSION: &str = "1.5";
+
 pub fn merge_pdfs(paths: &Vec<&str>) -> Result<Document, Error> {
+    merge_pdfs_with_outline_options(paths, OutlineOptions::default())
+}
+
+pub fn merge_pdfs_with_outline_options(
+    paths: &Vec<&str>,
+    outline_options: OutlineOptions,
+) -> Result<Document, Error> {
+    merge_pdfs_with_options(paths, outline_options, false)
+}
+
+// Dedup costs a full rewrite of every object's references, so it's opt-in rather than the
+// default: worthwhile for multi-file merges that share fonts/ICC profiles/images, wasted work
+// otherwise.
+pub fn merge_pdfs_with_options(
+    paths: &Vec<&str>,
+    outline_options: OutlineOptions,
+    dedup_resources: bool,
+) -> Result<Document, Error> {
+    let page_ranges = paths.iter().map(|_| PageRange::All).collect();
+    merge_pdfs_with_page_ranges_and_options(paths, page_ranges, outline_options, dedup_resources)
+}
+
+/// Merges only the selected pages of each input, in the order each [`PageRange`] requests,
+/// instead of every page in its original order.
+pub fn merge_pdfs_with_page_ranges(inputs: &[(&str, PageRange)]) -> Result<Document, Error> {
+    let paths: Vec<&str> = inputs.iter().map(|(path, _)| *path).collect();
+    let page_ranges = inputs.iter().map(|(_, range)| range.clone()).collect();
+
+    merge_pdfs_with_page_ranges_and_options(
+        &paths,
+        page_ranges,
+        OutlineOptions::default(),
+        false,
+    )
+}
+
+fn merge_pdfs_with_page_ranges_and_options(
+    paths: &[&str],
+    page_ranges: Vec<PageRange>,
+    outline_options: OutlineOptions,
+    dedup_resources: bool,
+) -> Result<Document, Error> {
     let documents = load_documents(paths)?;
-    let (documents_pages, documents_objects) = process_documents(documents)?;
+    let labels = paths.iter().map(|path| file_label(path)).collect();
+
+    merge_loaded_documents(
+        documents,
+        labels,
+        page_ranges,
+        DEFAULT_VERSION,
+        true,
+        outline_options,
+        dedup_resources,
+        MetadataOptions::InheritFirst,
+    )
+}
 
-    merge_documents(documents_pages, documents_objects)
+// Shared by the path-based `merge_pdfs*` functions and `MergeBuilder`, which can also supply
+// documents loaded from in-memory buffers or parsed ahead of time by the caller.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn merge_loaded_documents(
+    documents: Vec<Document>,
+    labels: Vec<String>,
+    page_ranges: Vec<PageRange>,
+    version: &str,
+    compress: bool,
+    outline_options: OutlineOptions,
+    dedup_resources: bool,
+    metadata_options: MetadataOptions,
+) -> Result<Document, Error> {
+    let ((mut documents_pages, page_order), mut documents_objects, source_outlines, source_infos) =
+        process_documents(documents, labels, page_ranges)?;
+
+    if dedup_resources {
+        dedup::dedup_resources(&mut documents_pages, &mut documents_objects);
+    }
+
+    merge_documents(
+        documents_pages,
+        page_order,
+        documents_objects,
+        source_outlines,
+        outline_options,
+        source_infos,
+        metadata_options,
+        version,
+        compress,
+    )
 }
 
-fn load_documents(paths: &Vec<&str>) -> Result<Vec<Document>, lopdf::Error> {
+fn load_documents(paths: &[&str]) -> Result<Vec<Document>, lopdf::Error> {
     paths.par_iter().map(Document::load).collect()
 }
 
+// Derives the bookmark label used when grouping a source file's outline under its own entry.
+pub(crate) fn file_label(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
 // Process each document and prepare them for merging
-fn process_documents(documents: Vec<Document>) -> Result<DocumentMappings, Error> {
+#[allow(clippy::type_complexity)]
+fn process_documents(
+    documents: Vec<Document>,
+    labels: Vec<String>,
+    page_ranges: Vec<PageRange>,
+) -> Result<
+    (
+        (BTreeMap<ObjectId, Object>, Vec<ObjectId>),
+        BTreeMap<ObjectId, Object>,
+        Vec<SourceOutline>,
+        Vec<Dictionary>,
+    ),
+    Error,
+> {
     let mut max_id = 1;
     let mut documents_pages = BTreeMap::new();
+    let mut page_order = Vec::new();
     let mut documents_objects = BTreeMap::new();
+    let mut source_outlines = Vec::new();
+    let mut source_infos = Vec::new();
 
-    for mut document in documents {
+    for ((mut document, label), page_range) in documents.into_iter().zip(labels).zip(page_ranges) {
         document.renumber_objects_with(max_id);
         max_id = document.max_id + 1;
 
-        documents_pages.extend(extract_pages(&document)?);
+        let (pages, order) = extract_pages(&document, &page_range, &mut max_id)?;
+        documents_pages.extend(pages);
+        page_order.extend(order);
+        source_outlines.extend(outline::extract_source_outline(&document, &label));
+        source_infos.push(metadata::extract_source_info(&document).unwrap_or_default());
         documents_objects.extend(document.objects);
     }
 
-    Ok((documents_pages, documents_objects))
+    Ok((
+        (documents_pages, page_order),
+        documents_objects,
+        source_outlines,
+        source_infos,
+    ))
+}
+
+// Attributes that PDF allows a `Page` to omit and inherit from an ancestor `Pages` node instead.
+const INHERITABLE_PAGE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+// Extracts the pages `page_range` selects, in the requested order. Returns the distinct page
+// objects (resolved against the document's page-tree inheritance) alongside the ordered list of
+// ids to thread into the merged `/Kids` array - kept separate since a range may select the same
+// page more than once, which a map alone can't represent. A repeat gets its own clone under a
+// fresh id minted from `next_id` (and bumped past), since a single page leaf dict has only one
+// `/Parent` and can't be referenced twice from `/Kids`.
+fn extract_pages(
+    document: &Document,
+    page_range: &PageRange,
+    next_id: &mut u32,
+) -> Result<(BTreeMap<ObjectId, Object>, Vec<ObjectId>), Error> {
+    let pages = document.get_pages();
+    let mut extracted = BTreeMap::new();
+    let mut order = Vec::new();
+
+    for page_number in page_range.resolve(pages.len() as u32) {
+        let Some(&object_id) = pages.get(&page_number) else {
+            continue; // out-of-range selection; nothing to include
+        };
+
+        let id = if extracted.contains_key(&object_id) {
+            let cloned_id = (*next_id, 0);
+            *next_id += 1;
+            cloned_id
+        } else {
+            object_id
+        };
+
+        if let std::collections::btree_map::Entry::Vacant(entry) = extracted.entry(id) {
+            let mut page = document.get_object(object_id)?.as_dict()?.clone();
+            resolve_inherited_attributes(document, &mut page);
+            entry.insert(Object::Dictionary(page));
+        }
+        order.push(id);
+    }
+
+    Ok((extracted, order))
 }
 
-// Extract pages from the document
-fn extract_pages(document: &Document) -> Result<BTreeMap<ObjectId, Object>, Error> {
-    document
-        .get_pages()
-        .into_values()
-        .map(|object_id| Ok((object_id, document.get_object(object_id)?.to_owned())))
-        .collect()
+// Merging flattens the page tree into a single level, so a page that relied on inheriting
+// `/Resources`, `/MediaBox`, `/CropBox` or `/Rotate` from an ancestor `Pages` node would otherwise
+// render with the wrong size, rotation, or missing fonts. Walk the original `/Parent` chain, still
+// reachable on the source document, and copy down the nearest ancestor's value for any key the
+// page itself doesn't define.
+fn resolve_inherited_attributes(document: &Document, page: &mut Dictionary) {
+    let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+
+    while let Some(parent_id) = parent {
+        let Ok(ancestor) = document.get_object(parent_id).and_then(Object::as_dict) else {
+            break;
+        };
+
+        for key in INHERITABLE_PAGE_KEYS {
+            if !page.has(key) {
+                if let Ok(value) = ancestor.get(key) {
+                    page.set(key, value.clone());
+                }
+            }
+        }
+
+        parent = ancestor.get(b"Parent").and_then(Object::as_reference).ok();
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn merge_documents(
     documents_pages: BTreeMap<ObjectId, Object>,
+    page_order: Vec<ObjectId>,
     documents_objects: BTreeMap<ObjectId, Object>,
+    source_outlines: Vec<SourceOutline>,
+    outline_options: OutlineOptions,
+    source_infos: Vec<Dictionary>,
+    metadata_options: MetadataOptions,
+    version: &str,
+    compress: bool,
 ) -> Result<Document, Error> {
-    let mut document = Document::with_version("1.5");
+    let mut document = Document::with_version(version);
 
     let ((catalog_id, catalog_object), (pages_id, pages_object)) =
         find_catalog_and_pages(&mut document, &documents_objects)?;
 
     insert_pages(&mut document, &documents_pages, pages_id)?;
-    update_pages_object(&mut document, &pages_object, documents_pages, pages_id)?;
-    update_catalog_object(&mut document, &catalog_object, catalog_id, pages_id)?;
+    update_pages_object(&mut document, &pages_object, &page_order, pages_id)?;
+
+    // All source objects so far were inserted directly into `document.objects`, which never
+    // bumps `max_id` - it's still 0. Seed it from the original ids before anything calls
+    // `new_object_id`/`add_object`, or the freshly allocated ids collide with (and overwrite)
+    // already-merged source objects. Read from `documents_objects`/`documents_pages` rather than
+    // `document.objects`: at this point `document` is still missing the catalog (only inserted
+    // later, by `update_catalog_object`), so seeding from it alone under-counts the highest id
+    // whenever the catalog owns it - as in a single-input merge whose catalog was added last.
+    document.max_id = documents_objects
+        .keys()
+        .chain(documents_pages.keys())
+        .map(|(id, _)| *id)
+        .max()
+        .unwrap_or(0);
+
+    let outlines_id = outline::merge_outlines(&mut document, source_outlines, outline_options);
+    update_catalog_object(&mut document, &catalog_object, catalog_id, pages_id, outlines_id)?;
 
     document.trailer.set("Root", catalog_id);
+    if let Some(info_id) = metadata::build_info(&mut document, metadata_options, &source_infos) {
+        document.trailer.set("Info", info_id);
+    }
     document.max_id = document.objects.len() as u32;
     document.renumber_objects();
-    document.compress();
+    if compress {
+        document.compress();
+    }
 
     Ok(document)
 }
@@ -89,7 +299,7 @@ fn find_catalog_and_pages(
                     pages_object = Some((*object_id, Object::Dictionary(new_dictionary)));
                 }
             }
-            "Page" | "Outlines" | "Outline" => {}
+            "Page" | "Outlines" => {}
             _ => {
                 document.objects.insert(*object_id, object.clone());
             }
@@ -98,8 +308,7 @@ fn find_catalog_and_pages(
 
     match (catalog_object, pages_object) {
         (Some(catalog), Some(pages)) => Ok((catalog, pages)),
-        _ => Err(lopdf::Error::from(io::Error::new(
-            io::ErrorKind::Other,
+        _ => Err(lopdf::Error::from(io::Error::other(
             "Failed to find catalog and pages objects",
         ))),
     }
@@ -132,16 +341,16 @@ fn insert_pages(
 fn update_pages_object(
     document: &mut Document,
     pages_object: &Object,
-    documents_pages: BTreeMap<ObjectId, Object>,
+    page_order: &[ObjectId],
     pages_id: ObjectId,
 ) -> Result<(), Error> {
     if let Ok(dictionary) = pages_object.as_dict() {
         let mut dictionary = dictionary.clone();
-        dictionary.set("Count", documents_pages.len() as u32);
+        dictionary.set("Count", page_order.len() as u32);
         dictionary.set(
             "Kids",
-            documents_pages
-                .keys()
+            page_order
+                .iter()
                 .map(|&object_id| Object::Reference(object_id))
                 .collect::<Vec<Object>>(),
         );
@@ -157,11 +366,19 @@ fn update_catalog_object(
     catalog_object: &Object,
     catalog_id: ObjectId,
     pages_id: ObjectId,
+    outlines_id: Option<ObjectId>,
 ) -> Result<(), Error> {
     if let Ok(dictionary) = catalog_object.as_dict() {
         let mut dictionary = dictionary.clone();
         dictionary.set("Pages", pages_id);
-        dictionary.remove(b"Outlines");
+        match outlines_id {
+            Some(outlines_id) => {
+                dictionary.set("Outlines", outlines_id);
+            }
+            None => {
+                dictionary.remove(b"Outlines");
+            }
+        }
         document
             .objects
             .insert(catalog_id, Object::Dictionary(dictionary));
@@ -172,6 +389,7 @@ fn update_catalog_object(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metadata::DocumentInfo;
     use lopdf::content::{Content, Operation};
     use lopdf::{dictionary, Document, Stream};
 
@@ -220,20 +438,182 @@ mod tests {
         doc
     }
 
+    // Attaches a single-bookmark `/Outlines` tree to `doc`'s catalog, then re-adds the catalog
+    // under a fresh id so it ends up owning the highest id in the document - as it does in
+    // `create_simple_pdf`'s output, which adds it last. Mutating the existing
+    // catalog dict in place (simpler, but wrong for this fixture) would leave the bookmark/outline
+    // objects - added after it - owning the highest ids instead.
+    fn add_bookmark(mut doc: Document, title: &str) -> Document {
+        let old_catalog_id = doc.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        let mut catalog = doc
+            .objects
+            .remove(&old_catalog_id)
+            .unwrap()
+            .as_dict()
+            .unwrap()
+            .clone();
+
+        let bookmark_id = doc.new_object_id();
+        let outlines_id = doc.new_object_id();
+        doc.objects.insert(
+            bookmark_id,
+            Object::Dictionary(dictionary! {
+                "Title" => Object::string_literal(title),
+                "Parent" => outlines_id,
+            }),
+        );
+        doc.objects.insert(
+            outlines_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Outlines",
+                "First" => bookmark_id,
+                "Last" => bookmark_id,
+                "Count" => 1,
+            }),
+        );
+
+        catalog.set("Outlines", outlines_id);
+        let catalog_id = doc.new_object_id();
+        doc.objects.insert(catalog_id, Object::Dictionary(catalog));
+        doc.trailer.set("Root", catalog_id);
+
+        doc
+    }
+
     #[test]
     fn test_process_documents_single_doc() {
         let doc = create_simple_pdf();
-        let result = process_documents(vec![doc]).unwrap();
-        assert_eq!(result.0.len(), 1);
-        assert!(!result.1.is_empty());
+        let ((documents_pages, page_order), documents_objects, _, _) = process_documents(
+            vec![doc],
+            vec!["doc".to_string()],
+            vec![PageRange::All],
+        )
+        .unwrap();
+        assert_eq!(documents_pages.len(), 1);
+        assert_eq!(page_order.len(), 1);
+        assert!(!documents_objects.is_empty());
+    }
+
+    #[test]
+    fn test_process_documents_repeated_page_gets_its_own_object() {
+        let doc = create_simple_pdf();
+        let ((documents_pages, page_order), _, _, _) = process_documents(
+            vec![doc],
+            vec!["doc".to_string()],
+            vec![PageRange::Pages(vec![1, 1])],
+        )
+        .unwrap();
+
+        // Each repeat must be a distinct object - a single page dict has one `/Parent` and can't
+        // be referenced from `/Kids` twice.
+        assert_eq!(page_order.len(), 2);
+        assert_ne!(page_order[0], page_order[1]);
+        assert_eq!(documents_pages.len(), 2);
     }
 
     #[test]
     fn test_merge_documents() {
         let doc1 = create_simple_pdf();
         let doc2 = create_simple_pdf();
-        let (documents_pages, documents_objects) = process_documents(vec![doc1, doc2]).unwrap();
-        let merged_doc = merge_documents(documents_pages, documents_objects).unwrap();
+        let ((documents_pages, page_order), documents_objects, source_outlines, source_infos) =
+            process_documents(
+                vec![doc1, doc2],
+                vec!["doc1".to_string(), "doc2".to_string()],
+                vec![PageRange::All, PageRange::All],
+            )
+            .unwrap();
+        let merged_doc = merge_documents(
+            documents_pages,
+            page_order,
+            documents_objects,
+            source_outlines,
+            OutlineOptions::default(),
+            source_infos,
+            MetadataOptions::default(),
+            DEFAULT_VERSION,
+            true,
+        )
+        .unwrap();
         assert_eq!(merged_doc.page_iter().count(), 2); // Vérifiez le nombre de pages après fusion
     }
+
+    #[test]
+    fn test_merge_documents_info_does_not_collide_with_page_objects() {
+        let doc = create_simple_pdf();
+        let ((documents_pages, page_order), documents_objects, source_outlines, source_infos) =
+            process_documents(vec![doc], vec!["doc".to_string()], vec![PageRange::All]).unwrap();
+        let objects_before_info = documents_objects.len();
+
+        let merged_doc = merge_documents(
+            documents_pages,
+            page_order,
+            documents_objects,
+            source_outlines,
+            OutlineOptions::default(),
+            source_infos,
+            MetadataOptions::Explicit(DocumentInfo {
+                title: Some("Merged".to_string()),
+                ..Default::default()
+            }),
+            DEFAULT_VERSION,
+            false,
+        )
+        .unwrap();
+
+        // Allocating the `/Info` object must get its own fresh id, not reuse one already taken by
+        // an already-merged source object - otherwise it silently overwrites that object instead
+        // of adding to the document.
+        assert_eq!(merged_doc.objects.len(), objects_before_info + 1);
+        let (_, page_id) = merged_doc.get_pages().into_iter().next().unwrap();
+        let content = merged_doc.get_and_decode_page_content(page_id).unwrap();
+        let text = content.operations.iter().find(|op| op.operator == "Tj");
+        assert!(text.is_some(), "page content stream was overwritten");
+    }
+
+    #[test]
+    fn test_merge_documents_single_input_with_bookmark() {
+        // `add_bookmark` re-adds the catalog last, so it owns the highest id in the document -
+        // the case that previously let a newly allocated outline id land exactly on the catalog's
+        // own slot, which `update_catalog_object` then overwrote, leaving the merged catalog's
+        // `/Outlines` pointing at itself. `merge_outlines` mints the root id and then, with
+        // `group_by_source`, a wrapper id, so either of them can land on the catalog's slot when
+        // `max_id` is seeded too low; both assertions below catch that.
+        let doc = add_bookmark(create_simple_pdf(), "Chapter 1");
+        let ((documents_pages, page_order), documents_objects, source_outlines, source_infos) =
+            process_documents(vec![doc], vec!["doc".to_string()], vec![PageRange::All]).unwrap();
+
+        let merged_doc = merge_documents(
+            documents_pages,
+            page_order,
+            documents_objects,
+            source_outlines,
+            OutlineOptions {
+                group_by_source: true,
+            },
+            source_infos,
+            MetadataOptions::default(),
+            DEFAULT_VERSION,
+            false,
+        )
+        .unwrap();
+
+        let catalog_id = merged_doc.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        let catalog = merged_doc.get_object(catalog_id).unwrap().as_dict().unwrap();
+        let outlines_id = catalog.get(b"Outlines").unwrap().as_reference().unwrap();
+        assert_ne!(
+            outlines_id, catalog_id,
+            "catalog's /Outlines must not point back at the catalog itself"
+        );
+
+        let outlines = merged_doc.get_object(outlines_id).unwrap().as_dict().unwrap();
+        assert_eq!(outlines.type_name().unwrap(), "Outlines");
+
+        let wrapper_id = outlines.get(b"First").unwrap().as_reference().unwrap();
+        let wrapper = merged_doc.get_object(wrapper_id).unwrap().as_dict().unwrap();
+        assert_eq!(wrapper.get(b"Title").unwrap().as_str().unwrap(), b"doc");
+
+        let first_id = wrapper.get(b"First").unwrap().as_reference().unwrap();
+        let bookmark = merged_doc.get_object(first_id).unwrap().as_dict().unwrap();
+        assert_eq!(bookmark.get(b"Title").unwrap().as_str().unwrap(), b"Chapter 1");
+    }
 }
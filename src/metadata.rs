@@ -0,0 +1,79 @@
+use chrono::Local;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+const CREATOR: &str = "pdf_merger";
+const PRODUCER: &str = "pdf_merger";
+
+/// Explicit values for the merged document's `/Info` dictionary.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+}
+
+/// Controls how the merged document's `/Info` dictionary is built. Whenever one is written, it
+/// always gets a fresh `/CreationDate`/`/ModDate` and this crate's `/Creator`/`/Producer`, since
+/// those describe the merge itself rather than any one input.
+#[derive(Debug, Clone, Default)]
+pub enum MetadataOptions {
+    /// Write no `/Info` dictionary at all.
+    #[default]
+    None,
+    /// Reuse the first source document's `/Info` dictionary (empty if it has none).
+    InheritFirst,
+    /// Write the given values instead of reading anything from the sources.
+    Explicit(DocumentInfo),
+}
+
+// Reads `document`'s `/Info` dictionary from its trailer, if any. Must run before the document's
+// objects are merged away, while `Info` still resolves against the document's own trailer.
+pub fn extract_source_info(document: &Document) -> Option<Dictionary> {
+    let info_id = document.trailer.get(b"Info").ok()?.as_reference().ok()?;
+    document.get_object(info_id).ok()?.as_dict().ok().cloned()
+}
+
+/// Builds the merged document's `/Info` dictionary, if any, inserts it into `document`, and
+/// returns its id so the caller can `trailer.set("Info", ...)`.
+pub fn build_info(
+    document: &mut Document,
+    options: MetadataOptions,
+    source_infos: &[Dictionary],
+) -> Option<ObjectId> {
+    let mut info = match options {
+        MetadataOptions::None => return None,
+        MetadataOptions::InheritFirst => source_infos.first().cloned().unwrap_or_default(),
+        MetadataOptions::Explicit(values) => explicit_info(values),
+    };
+
+    let now = pdf_date();
+    info.set("Creator", Object::string_literal(CREATOR));
+    info.set("Producer", Object::string_literal(PRODUCER));
+    info.set("CreationDate", Object::string_literal(now.clone()));
+    info.set("ModDate", Object::string_literal(now));
+
+    Some(document.add_object(Object::Dictionary(info)))
+}
+
+fn explicit_info(values: DocumentInfo) -> Dictionary {
+    let mut info = Dictionary::new();
+    if let Some(title) = values.title {
+        info.set("Title", Object::string_literal(title));
+    }
+    if let Some(author) = values.author {
+        info.set("Author", Object::string_literal(author));
+    }
+    if let Some(subject) = values.subject {
+        info.set("Subject", Object::string_literal(subject));
+    }
+    if let Some(keywords) = values.keywords {
+        info.set("Keywords", Object::string_literal(keywords));
+    }
+    info
+}
+
+// PDF date format, e.g. `D:20240131153045`.
+fn pdf_date() -> String {
+    format!("D:{}", Local::now().format("%Y%m%d%H%M%S"))
+}
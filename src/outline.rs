@@ -0,0 +1,151 @@
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+/// Controls how bookmarks from each source document are combined into the merged outline tree.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutlineOptions {
+    /// Wrap each source file's top-level bookmarks under an auto-generated bookmark named after
+    /// the file, instead of flattening them all into a single top-level list.
+    pub group_by_source: bool,
+}
+
+/// A source document's root `/Outlines` dictionary, captured before its object ids are dropped
+/// from the merged output. The item dictionaries it references via `/First`/`/Next` are left in
+/// place in the document's own objects and are merged in separately.
+pub struct SourceOutline {
+    label: String,
+    root: Dictionary,
+}
+
+// Reads `document`'s `/Outlines` dictionary, if any. Must run before the document's objects are
+// filtered, while `Root`/`Outlines` still resolve against the document's own trailer.
+pub fn extract_source_outline(document: &Document, label: &str) -> Option<SourceOutline> {
+    let catalog_id = document.trailer.get(b"Root").ok()?.as_reference().ok()?;
+    let catalog = document.get_object(catalog_id).ok()?.as_dict().ok()?;
+    let root_id = catalog.get(b"Outlines").ok()?.as_reference().ok()?;
+    let root = document.get_object(root_id).ok()?.as_dict().ok()?.clone();
+
+    Some(SourceOutline {
+        label: label.to_string(),
+        root,
+    })
+}
+
+/// Rebuilds a single unified `/Outlines` tree out of each source's root, inserting the new root
+/// (and, when `options.group_by_source` is set, one wrapper bookmark per source) into `document`.
+/// Returns the id of the new root, to be attached to the merged catalog, or `None` if none of the
+/// sources had any bookmarks.
+pub fn merge_outlines(
+    document: &mut Document,
+    sources: Vec<SourceOutline>,
+    options: OutlineOptions,
+) -> Option<ObjectId> {
+    let root_id = document.new_object_id();
+    let mut top_level = Vec::new();
+    let mut total_count = 0i64;
+
+    for source in sources {
+        let first = source.root.get(b"First").and_then(Object::as_reference).ok();
+        let last = source.root.get(b"Last").and_then(Object::as_reference).ok();
+        let count = source.root.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+
+        let (first, last) = match (first, last) {
+            (Some(first), Some(last)) => (first, last),
+            _ => continue, // source had an empty outline tree
+        };
+
+        if options.group_by_source {
+            let wrapper_id = document.new_object_id();
+            retarget_siblings_parent(document, first, wrapper_id);
+
+            let mut wrapper = Dictionary::new();
+            wrapper.set("Parent", Object::Reference(root_id));
+            wrapper.set("Title", Object::string_literal(source.label));
+            wrapper.set("First", Object::Reference(first));
+            wrapper.set("Last", Object::Reference(last));
+            wrapper.set("Count", count);
+            document
+                .objects
+                .insert(wrapper_id, Object::Dictionary(wrapper));
+
+            top_level.push(wrapper_id);
+            total_count += 1;
+        } else {
+            retarget_siblings_parent(document, first, root_id);
+            top_level.extend(collect_sibling_chain(document, first));
+            total_count += count;
+        }
+    }
+
+    if top_level.is_empty() {
+        return None;
+    }
+
+    link_siblings(document, &top_level);
+
+    let mut root = Dictionary::new();
+    root.set("Type", "Outlines");
+    root.set("First", Object::Reference(top_level[0]));
+    root.set("Last", Object::Reference(*top_level.last().unwrap()));
+    root.set("Count", total_count);
+    document.objects.insert(root_id, Object::Dictionary(root));
+
+    Some(root_id)
+}
+
+// Walks the `/Next` chain starting at `first_id`, pointing every sibling's `/Parent` at
+// `new_parent`. The original parent (the source document's own outline root) is not carried over.
+fn retarget_siblings_parent(document: &mut Document, first_id: ObjectId, new_parent: ObjectId) {
+    let mut current = Some(first_id);
+    while let Some(id) = current {
+        current = next_of(document, id);
+
+        if let Ok(Object::Dictionary(dict)) = document.get_object_mut(id) {
+            dict.set("Parent", Object::Reference(new_parent));
+        }
+    }
+}
+
+// Collects every sibling id in a `/Next` chain, in order, starting at `first_id`.
+fn collect_sibling_chain(document: &Document, first_id: ObjectId) -> Vec<ObjectId> {
+    let mut ids = Vec::new();
+    let mut current = Some(first_id);
+    while let Some(id) = current {
+        ids.push(id);
+        current = next_of(document, id);
+    }
+    ids
+}
+
+fn next_of(document: &Document, id: ObjectId) -> Option<ObjectId> {
+    document
+        .get_object(id)
+        .ok()?
+        .as_dict()
+        .ok()?
+        .get(b"Next")
+        .ok()?
+        .as_reference()
+        .ok()
+}
+
+// Re-threads `/Next`/`/Prev` across `ids` in order, dropping the boundary pointers at either end.
+fn link_siblings(document: &mut Document, ids: &[ObjectId]) {
+    for (index, &id) in ids.iter().enumerate() {
+        let Ok(Object::Dictionary(dict)) = document.get_object_mut(id) else {
+            continue;
+        };
+
+        match index.checked_sub(1).and_then(|i| ids.get(i)) {
+            Some(&prev) => dict.set("Prev", Object::Reference(prev)),
+            None => {
+                dict.remove(b"Prev");
+            }
+        };
+        match ids.get(index + 1) {
+            Some(&next) => dict.set("Next", Object::Reference(next)),
+            None => {
+                dict.remove(b"Next");
+            }
+        };
+    }
+}
@@ -0,0 +1,32 @@
+/// Selects and orders which pages of a source document go into a merge - dropping a cover page,
+/// extracting a single chapter, or reverse-collating a scanned stack, without a separate
+/// splitting tool.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PageRange {
+    /// Every page, in its original order.
+    #[default]
+    All,
+    /// A single 1-based page number.
+    Page(u32),
+    /// An inclusive range of 1-based page numbers, in ascending order.
+    Range(u32, u32),
+    /// An inclusive range of 1-based page numbers, in descending order.
+    ReverseRange(u32, u32),
+    /// An explicit, caller-ordered list of 1-based page numbers. May repeat or skip pages.
+    Pages(Vec<u32>),
+}
+
+impl PageRange {
+    /// Expands this selection into the ordered list of 1-based page numbers it refers to, against
+    /// a document with `page_count` pages. Numbers that turn out to be out of range are left in
+    /// the list; the caller resolves them against the document's actual pages and drops any miss.
+    pub fn resolve(&self, page_count: u32) -> Vec<u32> {
+        match self {
+            PageRange::All => (1..=page_count).collect(),
+            PageRange::Page(page) => vec![*page],
+            PageRange::Range(start, end) => (*start..=*end).collect(),
+            PageRange::ReverseRange(start, end) => (*start..=*end).rev().collect(),
+            PageRange::Pages(pages) => pages.clone(),
+        }
+    }
+}